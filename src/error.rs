@@ -0,0 +1,32 @@
+use std::{collections::TryReserveError as AllocTryReserveError, error, fmt};
+
+/// The error returned by [`HashMap::try_reserve`](crate::HashMap::try_reserve) and
+/// [`HashMap::try_insert`](crate::HashMap::try_insert) when growing the map would either
+/// overflow `usize` or the allocator itself fails, instead of panicking or aborting.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TryReserveError {
+    /// The requested capacity (current length plus the additional amount) overflows `usize`.
+    CapacityOverflow,
+    /// Growing the backing storage failed: either the allocator reported a failure, or the
+    /// request's size in bytes is more than a `Vec` can allocate.
+    AllocError(AllocTryReserveError),
+}
+
+impl From<AllocTryReserveError> for TryReserveError {
+    fn from(error: AllocTryReserveError) -> Self {
+        TryReserveError::AllocError(error)
+    }
+}
+
+impl fmt::Display for TryReserveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryReserveError::CapacityOverflow => {
+                write!(f, "the requested capacity exceeds `usize::MAX`")
+            }
+            TryReserveError::AllocError(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl error::Error for TryReserveError {}
@@ -0,0 +1,238 @@
+//! **This module does not implement the `no_std` packaging chunk0-7 asked for.** The request
+//! wanted a `core`/`hash32`-based map gated behind a `no_std` Cargo feature. This repo has no
+//! `Cargo.toml` anywhere in its history, so there is nowhere to declare that feature or the
+//! `hash32` dependency it needs — they cannot be added without a repo-level packaging change
+//! that is out of scope for a source-only change. What's implemented below is only the part of
+//! the request that's possible without one: a const-generic, non-allocating map, built on the
+//! same `std::hash` infrastructure as the rest of the crate. It is not `no_std` and makes no
+//! claim to be; treat the packaging half of chunk0-7 as unresolved, not done.
+
+use std::{
+    hash::{BuildHasher, Hash, Hasher},
+    mem,
+};
+
+use crate::RandomState;
+
+/// A slot in a [`FixedHashMap`]'s backing array. Plays the same `Empty`/`Deleted`/occupied role
+/// as [`crate::HashMap`]'s index table, except the key/value pair lives in the slot itself rather
+/// than in a separate entries vec, since there's no heap to put one on.
+enum FixedSlot<K, V> {
+    Empty,
+    Deleted,
+    Full(K, V),
+}
+
+/// The outcome of probing a [`FixedHashMap`]'s table: `found` is the slot whose occupant matches
+/// the key, if any; `insert_at` is where a new entry would go (the first tombstone seen, or the
+/// terminating empty slot), or `None` if the table is full and has no tombstone to reclaim.
+struct FixedProbe {
+    found: Option<usize>,
+    insert_at: Option<usize>,
+}
+
+/// A fixed-capacity, non-allocating hash map, in the spirit of heapless's `FnvIndexMap`.
+///
+/// Capacity is the compile-time constant `N`, which must be a power of two so that probing can
+/// mask instead of dividing, and is baked into the backing array rather than a `Vec`: there is no
+/// `resize`, and [`insert`](Self::insert) hands the pair straight back once the table is full
+/// instead of growing to make room.
+///
+/// Probing is the same quadratic (triangular-number) scheme [`crate::HashMap`] uses, bounded to
+/// at most `N` steps since the table can never have more than `N` slots to visit.
+///
+/// See this module's top-level doc comment: this type is built on `std::hash`, not `core`/
+/// `hash32`, and isn't gated behind a `no_std` feature — that part of chunk0-7 is unresolved.
+pub struct FixedHashMap<K, V, const N: usize, S = RandomState> {
+    table: [FixedSlot<K, V>; N],
+    len: usize,
+    hasher: S,
+}
+
+impl<K, V, const N: usize> FixedHashMap<K, V, N, RandomState> {
+    pub fn new() -> Self {
+        FixedHashMap::with_hasher(RandomState::default())
+    }
+}
+
+impl<K, V, const N: usize, S> FixedHashMap<K, V, N, S> {
+    pub fn with_hasher(hasher: S) -> Self {
+        const {
+            assert!(
+                N.is_power_of_two(),
+                "FixedHashMap capacity `N` must be a power of two"
+            );
+        }
+        FixedHashMap {
+            table: core::array::from_fn(|_| FixedSlot::Empty),
+            len: 0,
+            hasher,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<K, V, const N: usize, S> FixedHashMap<K, V, N, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Inserts `key`/`value`, returning the previous value for `key` if it was already present.
+    ///
+    /// If the table is full and has no tombstone to reclaim, the pair is handed straight back
+    /// instead of growing the table, since a fixed-capacity map has nowhere to grow into.
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, (K, V)> {
+        let probe = self.probe(&key);
+        if let Some(slot) = probe.found {
+            let old = match mem::replace(&mut self.table[slot], FixedSlot::Full(key, value)) {
+                FixedSlot::Full(_, old) => old,
+                FixedSlot::Empty | FixedSlot::Deleted => unreachable!("slot was not occupied"),
+            };
+            return Ok(Some(old));
+        }
+        match probe.insert_at {
+            Some(slot) => {
+                self.table[slot] = FixedSlot::Full(key, value);
+                self.len += 1;
+                Ok(None)
+            }
+            None => Err((key, value)),
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.probe(key).found.map(|slot| match &self.table[slot] {
+            FixedSlot::Full(_, value) => value,
+            FixedSlot::Empty | FixedSlot::Deleted => unreachable!("slot was not occupied"),
+        })
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let slot = self.probe(key).found?;
+        self.len -= 1;
+        match mem::replace(&mut self.table[slot], FixedSlot::Deleted) {
+            FixedSlot::Full(_, value) => Some(value),
+            FixedSlot::Empty | FixedSlot::Deleted => unreachable!("slot was not occupied"),
+        }
+    }
+
+    /// Probes the table with the same triangular offsets as [`crate::HashMap::probe`], capped at
+    /// `N` steps since a fixed-size table can never need more than that to visit every slot.
+    fn probe(&self, key: &K) -> FixedProbe {
+        let mask = N - 1;
+        let mut i = (self.hash(key) as usize) & mask;
+        let mut step = 1;
+        let mut tombstone = None;
+        let mut visited = 0;
+
+        while visited < N {
+            match &self.table[i] {
+                FixedSlot::Empty => {
+                    return FixedProbe {
+                        found: None,
+                        insert_at: Some(tombstone.unwrap_or(i)),
+                    };
+                }
+                FixedSlot::Deleted => {
+                    if tombstone.is_none() {
+                        tombstone = Some(i);
+                    }
+                }
+                FixedSlot::Full(k, _) if k == key => {
+                    return FixedProbe {
+                        found: Some(i),
+                        insert_at: Some(i),
+                    };
+                }
+                FixedSlot::Full(_, _) => {}
+            }
+            i = (i + step) & mask;
+            step += 1;
+            visited += 1;
+        }
+
+        FixedProbe {
+            found: None,
+            insert_at: tombstone,
+        }
+    }
+
+    fn hash(&self, key: &K) -> u64 {
+        let mut hasher = self.hasher.build_hasher();
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl<K, V, const N: usize> Default for FixedHashMap<K, V, N, RandomState> {
+    fn default() -> Self {
+        FixedHashMap::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_hashmap_insert_and_get() {
+        // Setup
+        let mut map: FixedHashMap<i32, &str, 4> = FixedHashMap::new();
+
+        // Scenario: Insert an item
+        let expected = map.insert(0, "a");
+        assert_eq!(expected, Ok(None));
+        assert_eq!(map.get(&0), Some(&"a"));
+
+        // Scenario: Insert a duplicate
+        let expected = map.insert(0, "b");
+        assert_eq!(expected, Ok(Some("a")));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn fixed_hashmap_remove() {
+        // Setup
+        let mut map: FixedHashMap<i32, &str, 4> = FixedHashMap::new();
+        map.insert(0, "a").unwrap();
+        map.insert(1, "b").unwrap();
+
+        // Scenario: Removing frees the slot for reuse without growing the table
+        assert_eq!(map.remove(&0), Some("a"));
+        assert_eq!(map.get(&0), None);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.insert(2, "c"), Ok(None));
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn fixed_hashmap_returns_the_pair_back_when_full() {
+        // Setup: fill every slot
+        let mut map: FixedHashMap<i32, i32, 4> = FixedHashMap::new();
+        for i in 0..4 {
+            map.insert(i, i).unwrap();
+        }
+
+        // Scenario: A new key has nowhere to go, so the pair comes straight back
+        assert_eq!(map.insert(4, 4), Err((4, 4)));
+        assert_eq!(map.len(), 4);
+
+        // Scenario: Overwriting an existing key still succeeds even while full
+        assert_eq!(map.insert(0, 10), Ok(Some(0)));
+    }
+}
@@ -1,71 +1,143 @@
 use std::{
     borrow::Borrow,
-    collections::hash_map::DefaultHasher,
-    hash::{Hash, Hasher},
-    mem,
+    hash::{BuildHasher, Hash},
+    iter::FusedIterator,
     ops::Index,
 };
 
-const INITIAL_BUCKETS: usize = 1;
-const BUCKET_SCALE_FACTOR: usize = 2;
-const RESIZE_NUM: usize = 3;
-const RESIZE_DEN: usize = 4;
-const fn resize_pred(items: usize, buckets: usize) -> bool {
+mod error;
+mod fixed;
+mod random_state;
+mod weak;
+
+pub use error::TryReserveError;
+pub use fixed::FixedHashMap;
+pub use random_state::RandomState;
+pub use weak::{WeakKey, WeakKeyEntry, WeakKeyHashMap, WeakKeyOccupiedEntry, WeakKeyVacantEntry};
+
+pub(crate) const INITIAL_BUCKETS: usize = 1;
+pub(crate) const BUCKET_SCALE_FACTOR: usize = 2;
+pub(crate) const RESIZE_NUM: usize = 3;
+pub(crate) const RESIZE_DEN: usize = 4;
+pub(crate) const fn resize_pred(items: usize, buckets: usize) -> bool {
     items >= RESIZE_NUM * buckets / RESIZE_DEN
 }
 
+/// A slot in the open-addressed index table. `Full` carries the index of the corresponding pair
+/// in `entries`; `Deleted` is a tombstone left behind by `remove` so that later probes keep
+/// walking past it instead of stopping early.
+#[derive(Clone, Copy, Debug)]
+enum Slot {
+    Empty,
+    Deleted,
+    Full(usize),
+}
+
+/// The outcome of probing the index table for a key: `found` is the table slot whose `Full`
+/// entry matches, if any; `insert_at` is where a new entry should go, which is either that same
+/// slot, the first tombstone seen along the way, or the empty slot that ended the probe.
+struct Probe {
+    found: Option<usize>,
+    insert_at: usize,
+}
+
+/// An insertion-ordered hash map.
+///
+/// `entries` is the single source of truth for both the key/value pairs and their iteration
+/// order; `table` is a flat, power-of-two-sized open-addressing index over it, so `for (k, v)
+/// in &map` yields pairs in the order they were inserted, unaffected by resizing. `remove` moves
+/// the last entry into the removed entry's position, so after a removal that one entry no longer
+/// sits in insertion order.
+///
+/// The index table uses quadratic (triangular-number) probing: `i, i+1, i+3, i+6, …` modulo the
+/// table size, which stays cache-friendly and avoids the primary clustering of linear probing
+/// while still visiting every slot of a power-of-two-sized table exactly once.
+///
+/// Hashing is pluggable via the `S: BuildHasher` parameter, defaulting to [`RandomState`] so
+/// that two maps hash the same keys differently and adversarial key sets can't force every
+/// entry into one bucket. Swap in a different `BuildHasher` (e.g. an FNV or aHash one) with
+/// [`HashMap::with_hasher`] when you don't need that protection and want raw speed instead.
 #[derive(Debug)]
-pub struct HashMap<K, V> {
-    buckets: Vec<Vec<(K, V)>>,
-    items: usize,
+pub struct HashMap<K, V, S = RandomState> {
+    entries: Vec<(K, V)>,
+    table: Vec<Slot>,
+    tombstones: usize,
+    hasher: S,
 }
 
-impl<K, V> HashMap<K, V> {
+impl<K, V> HashMap<K, V, RandomState> {
     pub fn new() -> Self {
+        HashMap::with_hasher(RandomState::default())
+    }
+}
+
+impl<K, V> Default for HashMap<K, V, RandomState> {
+    fn default() -> Self {
+        HashMap::new()
+    }
+}
+
+impl<K, V, S> HashMap<K, V, S> {
+    pub fn with_hasher(hasher: S) -> Self {
+        HashMap {
+            entries: Vec::new(),
+            table: Vec::new(),
+            tombstones: 0,
+            hasher,
+        }
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        let mut slots = INITIAL_BUCKETS;
+        while resize_pred(capacity, slots) {
+            slots *= BUCKET_SCALE_FACTOR;
+        }
         HashMap {
-            buckets: Vec::new(),
-            items: 0,
+            entries: Vec::with_capacity(capacity),
+            table: vec![Slot::Empty; if capacity == 0 { 0 } else { slots }],
+            tombstones: 0,
+            hasher,
         }
     }
 }
 
-impl<K, V> HashMap<K, V>
+impl<K, V, S> HashMap<K, V, S>
 where
     K: Hash + PartialEq + Eq,
+    S: BuildHasher,
 {
     pub fn insert(&mut self, key: K, value: V) -> Option<V> {
-        if resize_pred(self.items, self.buckets.len()) {
+        if resize_pred(self.entries.len() + self.tombstones, self.table.len()) {
             self.resize();
         }
-        let bucket = self.bucket(&key);
-        let bucket = &mut self.buckets[bucket];
-        match bucket.iter_mut().find(|(e_key, _)| *e_key == key) {
-            Some((_, e_value)) => Some(mem::replace(e_value, value)),
+        let probe = self.probe(&key);
+        match probe.found {
+            Some(slot) => {
+                let idx = self.table[slot].unwrap_full();
+                Some(std::mem::replace(&mut self.entries[idx].1, value))
+            }
             None => {
-                bucket.push((key, value));
-                self.items += 1;
+                self.occupy(probe.insert_at, key, value);
                 None
             }
         }
     }
 
-    pub fn entry(&mut self, key: K) -> Entry<K, V> {
-        if resize_pred(self.items, self.buckets.len()) {
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        if resize_pred(self.entries.len() + self.tombstones, self.table.len()) {
             self.resize();
         }
-        let bucket = self.bucket(&key);
-        if let Some(entry) = self.buckets[bucket]
-            .iter_mut()
-            .find(|&&mut (ref e_k, _)| e_k == &key)
-        {
+        let probe = self.probe(&key);
+        if let Some(slot) = probe.found {
+            let idx = self.table[slot].unwrap_full();
             Entry::Occupied(OccupiedEntry {
-                entry: unsafe { &mut *(entry as *mut _) },
+                entry: unsafe { &mut *(&mut self.entries[idx] as *mut _) },
             })
         } else {
             Entry::Vacant(VacantEntry {
-                key: key,
+                key,
                 map: self,
-                bucket,
+                slot: probe.insert_at,
             })
         }
     }
@@ -75,10 +147,9 @@ where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
     {
-        self.buckets[self.bucket(key.borrow())]
-            .iter()
-            .find(|(e_k, _)| e_k.borrow() == key)
-            .map(|&(_, ref v)| v)
+        self.probe(key)
+            .found
+            .map(|slot| &self.entries[self.table[slot].unwrap_full()].1)
     }
 
     pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
@@ -86,15 +157,31 @@ where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
     {
-        let bucket = self.bucket(&key);
-        let bucket = &mut self.buckets[bucket];
-        let position = bucket.iter().position(|(k, _)| k.borrow() == key)?;
-        self.items -= 1;
-        Some(bucket.swap_remove(position).1)
+        let slot = self.probe(key).found?;
+        let idx = self.table[slot].unwrap_full();
+        self.table[slot] = Slot::Deleted;
+        self.tombstones += 1;
+
+        // `swap_remove` is about to move the last entry into `idx` (unless `idx` is already
+        // last), so find the slot pointing at that last entry *before* it moves, then repoint it.
+        let last_idx = self.entries.len() - 1;
+        if idx != last_idx {
+            let moved_slot = self
+                .probe::<K>(&self.entries[last_idx].0)
+                .found
+                .expect("moved entry must still be present in the table");
+            self.table[moved_slot] = Slot::Full(idx);
+        }
+
+        Some(self.entries.swap_remove(idx).1)
     }
 
     pub fn len(&self) -> usize {
-        self.items
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
     }
 
     pub fn contains_key<Q>(&self, key: &Q) -> bool
@@ -105,40 +192,237 @@ where
         self.get(&key).is_some()
     }
 
+    pub fn iter(&self) -> HMIter<'_, K, V> {
+        HMIter {
+            iter: self.entries.iter(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            iter: self.entries.iter_mut(),
+        }
+    }
+
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { iter: self.iter() }
+    }
+
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { iter: self.iter() }
+    }
+
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            iter: self.iter_mut(),
+        }
+    }
+
+    /// Removes every entry, returning them in insertion order. Unlike `clear`-then-reinsert, the
+    /// index table is dropped rather than rehashed, so the map lazily rebuilds it from scratch
+    /// the next time it's needed.
+    pub fn drain(&mut self) -> Drain<'_, K, V> {
+        self.table.clear();
+        self.tombstones = 0;
+        Drain {
+            iter: self.entries.drain(..),
+        }
+    }
+
+    /// Keeps only the entries for which `f` returns `true`, then rebuilds the index table from
+    /// what's left, since dropping arbitrary entries out of `entries` would otherwise leave
+    /// `table` pointing at stale or shifted indices.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.entries.retain_mut(|(k, v)| f(k, v));
+        self.rebuild_table();
+    }
+
+    /// Reserves capacity for at least `additional` more entries without ever panicking or
+    /// aborting on allocation failure, unlike the implicit resizing `insert` does.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        // Tombstones occupy table slots just as much as live entries do, so they have to count
+        // toward `required` the same way `insert`/`entry` fold them into their resize check —
+        // otherwise a post-`try_reserve` insert could still find the table over its load factor
+        // and fall through to the infallible `resize()`.
+        let required = self
+            .entries
+            .len()
+            .checked_add(self.tombstones)
+            .and_then(|n| n.checked_add(additional))
+            .ok_or(TryReserveError::CapacityOverflow)?;
+        self.entries.try_reserve(additional)?;
+        let mut target_size = if self.table.is_empty() {
+            INITIAL_BUCKETS
+        } else {
+            self.table.len()
+        };
+        while resize_pred(required, target_size) {
+            target_size = target_size
+                .checked_mul(BUCKET_SCALE_FACTOR)
+                .ok_or(TryReserveError::CapacityOverflow)?;
+        }
+        if target_size != self.table.len() {
+            self.try_resize(target_size)?;
+        }
+        Ok(())
+    }
+
+    /// Like [`insert`](Self::insert), but reports an allocation failure instead of resizing
+    /// implicitly and potentially aborting the process.
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<Option<V>, TryReserveError> {
+        self.try_reserve(1)?;
+        Ok(self.insert(key, value))
+    }
+
+    fn occupy(&mut self, slot: usize, key: K, value: V) {
+        if let Slot::Deleted = self.table[slot] {
+            self.tombstones -= 1;
+        }
+        let idx = self.entries.len();
+        self.entries.push((key, value));
+        self.table[slot] = Slot::Full(idx);
+    }
+
     fn resize(&mut self) {
-        let target_size = match self.buckets.len() {
+        let target_size = match self.table.len() {
             0 => INITIAL_BUCKETS,
             x => x * BUCKET_SCALE_FACTOR,
         };
-        let mut new_buckets = Vec::with_capacity(target_size);
-        new_buckets.extend((0..target_size).map(|_| Vec::new()));
-        self.buckets
-            .iter_mut()
-            .flat_map(|b| b.drain(..))
-            .for_each(|(key, value)| {
-                let mut hasher = DefaultHasher::new();
-                key.hash(&mut hasher);
-                new_buckets[(hasher.finish() % target_size as u64) as usize].push((key, value));
-            });
+        let mut new_table = vec![Slot::Empty; target_size];
+        self.rehash_into(&mut new_table);
+        self.table = new_table;
+        self.tombstones = 0;
+    }
+
+    /// Rebuilds the index table from scratch for however many entries are currently in
+    /// `entries`, sized by the same 3/4 load policy `resize` uses for growth.
+    fn rebuild_table(&mut self) {
+        let mut target_size = INITIAL_BUCKETS;
+        while resize_pred(self.entries.len(), target_size) {
+            target_size *= BUCKET_SCALE_FACTOR;
+        }
+        let mut new_table = vec![Slot::Empty; if self.entries.is_empty() { 0 } else { target_size }];
+        self.rehash_into(&mut new_table);
+        self.table = new_table;
+        self.tombstones = 0;
+    }
 
-        mem::replace(&mut self.buckets, new_buckets);
+    fn try_resize(&mut self, target_size: usize) -> Result<(), TryReserveError> {
+        let mut new_table = Vec::new();
+        new_table.try_reserve_exact(target_size)?;
+        new_table.resize(target_size, Slot::Empty);
+        self.rehash_into(&mut new_table);
+        self.table = new_table;
+        self.tombstones = 0;
+        Ok(())
     }
 
-    fn bucket<Q>(&self, key: &Q) -> usize
+    fn rehash_into(&self, table: &mut [Slot]) {
+        if table.is_empty() {
+            return;
+        }
+        let mask = table.len() - 1;
+        for (idx, (key, _)) in self.entries.iter().enumerate() {
+            let mut i = (self.hash(key) as usize) & mask;
+            let mut step = 1;
+            while !matches!(table[i], Slot::Empty) {
+                i = (i + step) & mask;
+                step += 1;
+            }
+            table[i] = Slot::Full(idx);
+        }
+    }
+
+    /// Probes the index table with triangular offsets until it finds a slot whose entry matches
+    /// `key`, or an empty slot that proves `key` isn't present. Remembers the first tombstone
+    /// seen along the way so an absent key can be inserted there instead of at the empty slot
+    /// that terminates the probe.
+    fn probe<Q>(&self, key: &Q) -> Probe
     where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
     {
-        let mut hasher = DefaultHasher::new();
-        key.hash(&mut hasher);
-        (hasher.finish() % self.buckets.len() as u64) as usize
+        if self.table.is_empty() {
+            return Probe {
+                found: None,
+                insert_at: 0,
+            };
+        }
+
+        let mask = self.table.len() - 1;
+        let mut i = (self.hash(key) as usize) & mask;
+        let mut step = 1;
+        let mut tombstone = None;
+        let mut visited = 0;
+
+        // Bounded to `table.len()` steps: quadratic probing over a power-of-two-sized table
+        // visits every slot exactly once in that many steps, so if neither a match nor an
+        // `Empty` slot has turned up by then, none exists. That happens when the load policy lets
+        // inserts fill a small table completely (two entries in a 2-slot table), and whenever
+        // `remove` has left the table at 100% `Full`/`Deleted` occupancy (it turns `Full` into
+        // `Deleted` without ever shrinking or re-triggering a resize), so an unbounded loop here
+        // would spin forever on a lookup for an absent key.
+        while visited < self.table.len() {
+            match self.table[i] {
+                Slot::Empty => {
+                    return Probe {
+                        found: None,
+                        insert_at: tombstone.unwrap_or(i),
+                    };
+                }
+                Slot::Deleted => {
+                    if tombstone.is_none() {
+                        tombstone = Some(i);
+                    }
+                }
+                Slot::Full(idx) if self.entries[idx].0.borrow() == key => {
+                    return Probe {
+                        found: Some(i),
+                        insert_at: i,
+                    };
+                }
+                Slot::Full(_) => {}
+            }
+            i = (i + step) & mask;
+            step += 1;
+            visited += 1;
+        }
+
+        // Every slot has been visited with no match and no `Empty` slot: report "not found".
+        // `insert_at` only matters to callers about to write a new entry, and `insert`/`entry`
+        // always resize before probing, so the table never actually reaches this state on the
+        // path that would use it.
+        Probe {
+            found: None,
+            insert_at: tombstone.unwrap_or(i),
+        }
+    }
+
+    fn hash<Q>(&self, key: &Q) -> u64
+    where
+        Q: Hash + ?Sized,
+    {
+        self.hasher.hash_one(key)
+    }
+}
+
+impl Slot {
+    fn unwrap_full(self) -> usize {
+        match self {
+            Slot::Full(idx) => idx,
+            Slot::Empty | Slot::Deleted => unreachable!("slot was not occupied"),
+        }
     }
 }
 
-impl<'a, K, Q, V> Index<&'a Q> for HashMap<K, V>
+impl<K, Q, V, S> Index<&Q> for HashMap<K, V, S>
 where
     K: Eq + Hash + Borrow<Q>,
     Q: Eq + Hash + ?Sized,
+    S: BuildHasher,
 {
     type Output = V;
 
@@ -148,76 +432,260 @@ where
 }
 
 pub struct HMIter<'a, K: 'a, V: 'a> {
-    map: &'a HashMap<K, V>,
-    bucket: usize,
-    item: usize,
+    iter: std::slice::Iter<'a, (K, V)>,
 }
 
 impl<'a, K, V> Iterator for HMIter<'a, K, V> {
     type Item = (&'a K, &'a V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            match self.map.buckets.get(self.bucket) {
-                Some(bucket) => match bucket.get(self.item) {
-                    Some(&(ref k, ref v)) => {
-                        self.item += 1;
-                        break Some((k, v));
-                    }
-                    None => {
-                        self.bucket += 1;
-                        self.item = 0;
-                        continue;
-                    }
-                },
-                None => break None,
-            }
-        }
+        self.iter.next().map(|(k, v)| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
     }
 }
 
-impl<'a, K, V> IntoIterator for &'a HashMap<K, V> {
+impl<'a, K, V> ExactSizeIterator for HMIter<'a, K, V> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<'a, K, V> FusedIterator for HMIter<'a, K, V> {}
+
+impl<'a, K, V, S> IntoIterator for &'a HashMap<K, V, S> {
     type Item = (&'a K, &'a V);
     type IntoIter = HMIter<'a, K, V>;
 
     fn into_iter(self) -> <Self as IntoIterator>::IntoIter {
         HMIter {
-            map: self,
-            bucket: 0,
-            item: 0,
+            iter: self.entries.iter(),
+        }
+    }
+}
+
+pub struct IterMut<'a, K: 'a, V: 'a> {
+    iter: std::slice::IterMut<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, v)| (&*k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for IterMut<'a, K, V> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<'a, K, V> FusedIterator for IterMut<'a, K, V> {}
+
+impl<'a, K, V, S> IntoIterator for &'a mut HashMap<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> <Self as IntoIterator>::IntoIter {
+        IterMut {
+            iter: self.entries.iter_mut(),
+        }
+    }
+}
+
+pub struct Keys<'a, K: 'a, V: 'a> {
+    iter: HMIter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, _)| k)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Keys<'a, K, V> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<'a, K, V> FusedIterator for Keys<'a, K, V> {}
+
+pub struct Values<'a, K: 'a, V: 'a> {
+    iter: HMIter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(_, v)| v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Values<'a, K, V> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<'a, K, V> FusedIterator for Values<'a, K, V> {}
+
+pub struct ValuesMut<'a, K: 'a, V: 'a> {
+    iter: IterMut<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(_, v)| v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for ValuesMut<'a, K, V> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<'a, K, V> FusedIterator for ValuesMut<'a, K, V> {}
+
+pub struct IntoIter<K, V> {
+    iter: std::vec::IntoIter<(K, V)>,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<K, V> FusedIterator for IntoIter<K, V> {}
+
+impl<K, V, S> IntoIterator for HashMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> <Self as IntoIterator>::IntoIter {
+        IntoIter {
+            iter: self.entries.into_iter(),
+        }
+    }
+}
+
+pub struct Drain<'a, K: 'a, V: 'a> {
+    iter: std::vec::Drain<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Drain<'a, K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Drain<'a, K, V> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<'a, K, V> FusedIterator for Drain<'a, K, V> {}
+
+impl<K, V, S> Extend<(K, V)> for HashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
         }
     }
 }
 
+impl<K, V, S> FromIterator<(K, V)> for HashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = HashMap::with_hasher(S::default());
+        map.extend(iter);
+        map
+    }
+}
+
 pub struct OccupiedEntry<'a, K: 'a, V: 'a> {
     entry: &'a mut (K, V),
 }
 
-pub struct VacantEntry<'a, K: 'a, V: 'a> {
+pub struct VacantEntry<'a, K: 'a, V: 'a, S: 'a> {
     key: K,
-    map: &'a mut HashMap<K, V>,
-    bucket: usize,
+    map: &'a mut HashMap<K, V, S>,
+    slot: usize,
 }
 
-impl<'a, K, V> VacantEntry<'a, K, V>
+impl<'a, K, V, S> VacantEntry<'a, K, V, S>
 where
     K: Eq + Hash,
+    S: BuildHasher,
 {
     fn insert(self, value: V) -> &'a mut V {
-        self.map.buckets[self.bucket].push((self.key, value));
-        self.map.items += 1;
-        &mut self.map.buckets[self.bucket].last_mut().unwrap().1
+        self.map.occupy(self.slot, self.key, value);
+        &mut self.map.entries.last_mut().unwrap().1
     }
 }
 
-pub enum Entry<'a, K: 'a, V: 'a> {
+pub enum Entry<'a, K: 'a, V: 'a, S: 'a> {
     Occupied(OccupiedEntry<'a, K, V>),
-    Vacant(VacantEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V, S>),
 }
 
-impl<'a, K, V> Entry<'a, K, V>
+impl<'a, K, V, S> Entry<'a, K, V, S>
 where
     K: Eq + Hash,
+    S: BuildHasher,
 {
     pub fn or_insert(self, value: V) -> &'a mut V {
         match self {
@@ -303,6 +771,70 @@ mod tests {
         assert!(expected.is_none());
     }
 
+    #[test]
+    fn hashmap_remove_then_reinsert_via_tombstone() {
+        // Setup
+        let mut map = HashMap::new();
+        map.insert(0, "a");
+        map.insert(1, "b");
+        map.insert(2, "c");
+        map.remove(&1);
+
+        // Scenario: Re-inserting a removed key reuses its tombstone rather than growing forever
+        let expected = map.insert(1, "bb");
+        assert!(expected.is_none());
+        assert_eq!(map.get(&1), Some(&"bb"));
+        assert_eq!(map.len(), 3);
+    }
+
+    #[test]
+    fn hashmap_lookup_terminates_on_a_fully_occupied_table() {
+        // Setup: a 2-slot table left 100% `Full` by two inserts, with no `Empty` slot ever
+        // created
+        let mut map = HashMap::new();
+        map.insert(1, "a");
+        map.insert(2, "b");
+
+        // Scenario: Looking up a key that isn't present must return instead of spinning forever
+        // probing a table with no `Empty` slot to stop at
+        assert_eq!(map.get(&3), None);
+    }
+
+    #[test]
+    fn hashmap_lookup_terminates_after_removing_everything() {
+        // Setup: `remove` turns `Full` into `Deleted` without ever shrinking or re-triggering a
+        // resize, so removing every entry leaves the table 100% `Deleted` with no `Empty` slot
+        let mut map = HashMap::new();
+        map.insert(1, "a");
+        map.remove(&1);
+
+        // Scenario: A lookup for an absent key against an all-tombstone table must return
+        // instead of spinning forever
+        assert_eq!(map.get(&2), None);
+    }
+
+    #[test]
+    fn hashmap_many_inserts_and_removals() {
+        // Setup: enough churn to force several resizes and tombstone reuse
+        let mut map = HashMap::new();
+        for i in 0..200 {
+            map.insert(i, i * 2);
+        }
+        for i in (0..200).step_by(2) {
+            map.remove(&i);
+        }
+
+        // Scenario: every surviving key is still reachable and every removed key is gone
+        for i in 0..200 {
+            if i % 2 == 0 {
+                assert_eq!(map.get(&i), None);
+            } else {
+                assert_eq!(map.get(&i), Some(&(i * 2)));
+            }
+        }
+        assert_eq!(map.len(), 100);
+    }
+
     #[test]
     fn hashmap_len() {
         // Setup
@@ -363,6 +895,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn hashmap_preserves_insertion_order() {
+        // Setup
+        let mut map = HashMap::new();
+        map.insert(3, "d");
+        map.insert(1, "b");
+        map.insert(2, "c");
+        map.insert(0, "a");
+
+        // Scenario: Iteration order matches insertion order, not bucket order
+        let keys: Vec<_> = map.into_iter().map(|(k, _)| k).collect();
+        assert_eq!(keys, vec![3, 1, 2, 0]);
+    }
+
+    #[test]
+    fn hashmap_remove_swaps_last_entry_into_gap() {
+        // Setup
+        let mut map = HashMap::new();
+        map.insert(0, "a");
+        map.insert(1, "b");
+        map.insert(2, "c");
+        map.insert(3, "d");
+
+        // Scenario: Removing from the middle shifts the last entry into the gap but leaves the
+        // order of everything else untouched
+        map.remove(&1);
+        let keys: Vec<_> = map.into_iter().map(|(k, _)| k).collect();
+        assert_eq!(keys, vec![0, 3, 2]);
+    }
+
     #[test]
     fn entry_or_insert() {
         // Setup
@@ -388,6 +950,191 @@ mod tests {
         assert_eq!(map["poneyland"], "hoho".to_string());
     }
 
+    #[test]
+    fn hashmap_with_custom_hasher() {
+        // Setup: a deterministic hasher in place of the default randomized one
+        let mut map: HashMap<_, _, std::hash::BuildHasherDefault<std::collections::hash_map::DefaultHasher>> =
+            HashMap::with_hasher(Default::default());
+
+        // Scenario: the map still behaves correctly with a swapped-in `BuildHasher`
+        map.insert(0, "a");
+        map.insert(1, "b");
+        assert_eq!(map.get(&0), Some(&"a"));
+        assert_eq!(map.remove(&1), Some("b"));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn hashmap_try_insert() {
+        // Setup
+        let mut map = HashMap::new();
+
+        // Scenario: A fallible insert behaves just like `insert` on the happy path
+        let expected = map.try_insert(42, "a");
+        assert_eq!(expected, Ok(None));
+        assert_eq!(map.get(&42), Some(&"a"));
+    }
+
+    #[test]
+    fn hashmap_try_reserve_grows_capacity() {
+        // Setup
+        let mut map: HashMap<i32, i32> = HashMap::new();
+
+        // Scenario: Reserving ahead of time lets many inserts follow without further growth
+        map.try_reserve(100).unwrap();
+        for i in 0..100 {
+            map.insert(i, i);
+        }
+        assert_eq!(map.len(), 100);
+    }
+
+    #[test]
+    fn hashmap_try_reserve_reports_capacity_overflow() {
+        // Setup
+        let mut map: HashMap<i32, i32> = HashMap::new();
+        map.insert(0, 0);
+
+        // Scenario: Asking for more than `usize::MAX` entries reports an error instead of panicking
+        assert_eq!(
+            map.try_reserve(usize::MAX),
+            Err(TryReserveError::CapacityOverflow)
+        );
+    }
+
+    #[test]
+    fn hashmap_try_reserve_accounts_for_tombstones() {
+        // Setup: 3 tombstones and 0 live entries, which would otherwise still trip the ordinary
+        // 3/4 load threshold against the table's current size
+        let mut map: HashMap<i32, i32> = HashMap::new();
+        for i in 0..3 {
+            map.insert(i, i);
+        }
+        for i in 0..3 {
+            map.remove(&i);
+        }
+        assert_eq!(map.len(), 0);
+
+        // Scenario: `try_reserve` must grow the table enough to absorb both the tombstones it
+        // already holds and the requested headroom, so nothing afterward falls through to the
+        // infallible `resize()` it exists to avoid
+        map.try_reserve(1).unwrap();
+        assert!(!resize_pred(
+            map.entries.len() + map.tombstones,
+            map.table.len()
+        ));
+    }
+
+    #[test]
+    fn hashmap_iter_mut() {
+        // Setup
+        let mut map = HashMap::new();
+        map.insert(0, 1);
+        map.insert(1, 2);
+        map.insert(2, 3);
+
+        // Scenario: Mutating through `iter_mut` is visible on subsequent reads
+        for (_, v) in map.iter_mut() {
+            *v *= 10;
+        }
+        assert_eq!(map.get(&1), Some(&20));
+    }
+
+    #[test]
+    fn hashmap_keys_and_values() {
+        // Setup
+        let mut map = HashMap::new();
+        map.insert(0, "a");
+        map.insert(1, "b");
+
+        // Scenario: `keys` and `values` project insertion order without the other half of the pair
+        assert_eq!(map.keys().collect::<Vec<_>>(), vec![&0, &1]);
+        assert_eq!(map.values().collect::<Vec<_>>(), vec![&"a", &"b"]);
+    }
+
+    #[test]
+    fn hashmap_values_mut() {
+        // Setup
+        let mut map = HashMap::new();
+        map.insert(0, 1);
+        map.insert(1, 2);
+
+        // Scenario: `values_mut` lets every value be updated in place
+        for v in map.values_mut() {
+            *v += 1;
+        }
+        assert_eq!(map.values().collect::<Vec<_>>(), vec![&2, &3]);
+    }
+
+    #[test]
+    fn hashmap_owning_into_iter() {
+        // Setup
+        let mut map = HashMap::new();
+        map.insert(0, "a");
+        map.insert(1, "b");
+
+        // Scenario: Consuming the map by value yields owned pairs in insertion order
+        let pairs: Vec<_> = map.into_iter().collect();
+        assert_eq!(pairs, vec![(0, "a"), (1, "b")]);
+    }
+
+    #[test]
+    fn hashmap_drain_empties_the_map() {
+        // Setup
+        let mut map = HashMap::new();
+        map.insert(0, "a");
+        map.insert(1, "b");
+
+        // Scenario: Draining yields every pair and leaves the map empty but reusable
+        let drained: Vec<_> = map.drain().collect();
+        assert_eq!(drained, vec![(0, "a"), (1, "b")]);
+        assert_eq!(map.len(), 0);
+
+        // Scenario: The map still works after being drained
+        map.insert(2, "c");
+        assert_eq!(map.get(&2), Some(&"c"));
+    }
+
+    #[test]
+    fn hashmap_retain_keeps_matching_entries() {
+        // Setup
+        let mut map = HashMap::new();
+        for i in 0..10 {
+            map.insert(i, i);
+        }
+
+        // Scenario: Only even keys survive, and the surviving entries are still all reachable
+        map.retain(|k, _| k % 2 == 0);
+        assert_eq!(map.len(), 5);
+        for i in 0..10 {
+            if i % 2 == 0 {
+                assert_eq!(map.get(&i), Some(&i));
+            } else {
+                assert_eq!(map.get(&i), None);
+            }
+        }
+    }
+
+    #[test]
+    fn hashmap_from_iterator() {
+        // Scenario: `collect` builds a map from an iterator of pairs
+        let map: HashMap<_, _> = vec![(0, "a"), (1, "b"), (2, "c")].into_iter().collect();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get(&1), Some(&"b"));
+    }
+
+    #[test]
+    fn hashmap_extend() {
+        // Setup
+        let mut map = HashMap::new();
+        map.insert(0, "a");
+
+        // Scenario: `extend` inserts every pair from the iterator, overwriting existing keys
+        map.extend(vec![(0, "aa"), (1, "b")]);
+        assert_eq!(map.get(&0), Some(&"aa"));
+        assert_eq!(map.get(&1), Some(&"b"));
+        assert_eq!(map.len(), 2);
+    }
+
     #[test]
     fn entry_or_default() {
         let mut map: HashMap<&str, Option<u32>> = HashMap::new();
@@ -0,0 +1,66 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{BuildHasher, Hash, Hasher},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::SystemTime,
+};
+
+/// The default [`BuildHasher`] for [`HashMap`](crate::HashMap).
+///
+/// Every `RandomState` is seeded with its own pair of pseudo-random keys, derived from a counter,
+/// the current time and a stack address, so two maps hash the same keys differently and an
+/// attacker who knows the
+/// hash algorithm can't choose keys that all land in one bucket.
+#[derive(Clone, Debug)]
+pub struct RandomState {
+    k0: u64,
+    k1: u64,
+}
+
+impl RandomState {
+    pub fn new() -> Self {
+        RandomState {
+            k0: random_seed(),
+            k1: random_seed(),
+        }
+    }
+}
+
+impl Default for RandomState {
+    fn default() -> Self {
+        RandomState::new()
+    }
+}
+
+impl BuildHasher for RandomState {
+    type Hasher = DefaultHasher;
+
+    fn build_hasher(&self) -> DefaultHasher {
+        // `DefaultHasher::new()` always starts from the same fixed state, so every hasher built
+        // from a given `RandomState` is primed with that state's keys before any caller data is
+        // fed in, which is what makes two `HashMap`s hash identical keys differently.
+        let mut hasher = DefaultHasher::new();
+        hasher.write_u64(self.k0);
+        hasher.write_u64(self.k1);
+        hasher
+    }
+}
+
+/// Derives a process-local random-ish seed from a monotonic counter, the current time, and a
+/// stack address, without pulling in an external RNG crate.
+fn random_seed() -> u64 {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed) as u64;
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    let stack_addr = &count as *const u64 as u64;
+
+    let mut hasher = DefaultHasher::new();
+    count.hash(&mut hasher);
+    nanos.hash(&mut hasher);
+    stack_addr.hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,447 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    mem,
+    ops::Deref,
+    rc::{Rc, Weak as RcWeak},
+    sync::{Arc, Weak as ArcWeak},
+};
+
+use crate::{resize_pred, BUCKET_SCALE_FACTOR, INITIAL_BUCKETS};
+
+/// Pairs a weak pointer type with the strong pointer it is downgraded from, so
+/// [`WeakKeyHashMap`] can store the weak half as a key while still being able to upgrade it back
+/// to something hashable and comparable for lookups.
+pub trait WeakKey: Sized {
+    type Strong: Deref;
+
+    fn downgrade(strong: &Self::Strong) -> Self;
+    fn upgrade(&self) -> Option<Self::Strong>;
+}
+
+impl<T: ?Sized> WeakKey for RcWeak<T> {
+    type Strong = Rc<T>;
+
+    fn downgrade(strong: &Rc<T>) -> Self {
+        Rc::downgrade(strong)
+    }
+
+    fn upgrade(&self) -> Option<Rc<T>> {
+        RcWeak::upgrade(self)
+    }
+}
+
+impl<T: ?Sized> WeakKey for ArcWeak<T> {
+    type Strong = Arc<T>;
+
+    fn downgrade(strong: &Arc<T>) -> Self {
+        Arc::downgrade(strong)
+    }
+
+    fn upgrade(&self) -> Option<Arc<T>> {
+        ArcWeak::upgrade(self)
+    }
+}
+
+/// A hash map whose keys are held weakly and compared/hashed by the pointee value, so entries
+/// disappear on their own once the last strong owner of the key drops it.
+///
+/// Each slot caches the key's hash code alongside the weak pointer and value, because a dead
+/// weak pointer can no longer be upgraded to hash its pointee. `insert` and `entry` upgrade each
+/// candidate they probe: a failed upgrade means the slot is expired and gets reclaimed on the
+/// spot, while a successful one is compared against the query by value. `get` probes the same
+/// way but, being `&self`, can't reclaim what it finds expired in place; it simply treats a dead
+/// upgrade as a miss. Call [`remove_expired`](Self::remove_expired) to sweep out entries the map
+/// hasn't happened to probe past yet.
+pub struct WeakKeyHashMap<K: WeakKey, V> {
+    buckets: Vec<Vec<(K, V, u64)>>,
+    live: usize,
+}
+
+impl<K: WeakKey, V> WeakKeyHashMap<K, V> {
+    pub fn new() -> Self {
+        WeakKeyHashMap {
+            buckets: Vec::new(),
+            live: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.live
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.live == 0
+    }
+}
+
+impl<K, V> WeakKeyHashMap<K, V>
+where
+    K: WeakKey,
+    <K::Strong as Deref>::Target: Hash + Eq,
+{
+    pub fn insert(&mut self, key: &K::Strong, value: V) -> Option<V> {
+        if resize_pred(self.live, self.buckets.len()) {
+            self.resize();
+        }
+        let hash = Self::hash_of(key);
+        let bucket = self.bucket_mut(hash);
+
+        let mut i = 0;
+        let mut reclaimed = 0usize;
+        while i < bucket.len() {
+            if bucket[i].2 != hash {
+                i += 1;
+                continue;
+            }
+            match bucket[i].0.upgrade() {
+                Some(strong) if *strong == **key => {
+                    return Some(mem::replace(&mut bucket[i].1, value));
+                }
+                Some(_) => i += 1,
+                None => {
+                    // Expired since we last looked: reclaim the slot instead of skipping past it.
+                    bucket.swap_remove(i);
+                    reclaimed += 1;
+                }
+            }
+        }
+
+        bucket.push((K::downgrade(key), value, hash));
+        self.live = self.live - reclaimed + 1;
+        None
+    }
+
+    /// Looks up `key`'s slot, reclaiming expired entries in its bucket that share `key`'s cached
+    /// hash along the way, just like [`insert`](Self::insert) does; [`get`](Self::get) skips
+    /// such entries without reclaiming them.
+    pub fn entry(&mut self, key: &K::Strong) -> WeakKeyEntry<'_, K, V> {
+        if resize_pred(self.live, self.buckets.len()) {
+            self.resize();
+        }
+        let hash = Self::hash_of(key);
+        let bucket_idx = (hash % self.buckets.len() as u64) as usize;
+        let bucket = &mut self.buckets[bucket_idx];
+
+        let mut i = 0;
+        let mut reclaimed = 0usize;
+        let mut found = None;
+        while i < bucket.len() {
+            if bucket[i].2 != hash {
+                i += 1;
+                continue;
+            }
+            match bucket[i].0.upgrade() {
+                Some(strong) if *strong == **key => {
+                    found = Some(i);
+                    break;
+                }
+                Some(_) => i += 1,
+                None => {
+                    bucket.swap_remove(i);
+                    reclaimed += 1;
+                }
+            }
+        }
+        self.live -= reclaimed;
+
+        match found {
+            Some(idx) => WeakKeyEntry::Occupied(WeakKeyOccupiedEntry {
+                value: &mut self.buckets[bucket_idx][idx].1,
+            }),
+            None => WeakKeyEntry::Vacant(WeakKeyVacantEntry {
+                weak: K::downgrade(key),
+                hash,
+                bucket: bucket_idx,
+                map: self,
+            }),
+        }
+    }
+
+    pub fn get(&self, key: &<K::Strong as Deref>::Target) -> Option<&V> {
+        if self.buckets.is_empty() {
+            return None;
+        }
+        let hash = Self::hash_of(key);
+        self.buckets[(hash % self.buckets.len() as u64) as usize]
+            .iter()
+            .find(|(weak, _, hc)| {
+                *hc == hash && weak.upgrade().is_some_and(|strong| *strong == *key)
+            })
+            .map(|(_, v, _)| v)
+    }
+
+    pub fn contains_key(&self, key: &<K::Strong as Deref>::Target) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Sweeps every bucket, dropping entries whose weak key no longer upgrades.
+    pub fn remove_expired(&mut self) {
+        for bucket in &mut self.buckets {
+            bucket.retain(|(weak, _, _)| weak.upgrade().is_some());
+        }
+        self.live = self.buckets.iter().map(Vec::len).sum();
+    }
+
+    fn hash_of(key: &<K::Strong as Deref>::Target) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn bucket_mut(&mut self, hash: u64) -> &mut Vec<(K, V, u64)> {
+        let bucket = (hash % self.buckets.len() as u64) as usize;
+        &mut self.buckets[bucket]
+    }
+
+    fn resize(&mut self) {
+        let target_size = match self.buckets.len() {
+            0 => INITIAL_BUCKETS,
+            x => x * BUCKET_SCALE_FACTOR,
+        };
+        let mut new_buckets = Vec::with_capacity(target_size);
+        new_buckets.extend((0..target_size).map(|_| Vec::new()));
+
+        // Rehashing is also a natural opportunity to drop anything that expired in the meantime.
+        for (weak, value, hash) in self.buckets.drain(..).flatten() {
+            if weak.upgrade().is_some() {
+                new_buckets[(hash % target_size as u64) as usize].push((weak, value, hash));
+            }
+        }
+
+        self.buckets = new_buckets;
+        self.live = self.buckets.iter().map(Vec::len).sum();
+    }
+}
+
+impl<K: WeakKey, V> Default for WeakKeyHashMap<K, V> {
+    fn default() -> Self {
+        WeakKeyHashMap::new()
+    }
+}
+
+pub struct WeakKeyIter<'a, K: 'a, V: 'a> {
+    buckets: &'a [Vec<(K, V, u64)>],
+    bucket: usize,
+    item: usize,
+}
+
+impl<'a, K, V> Iterator for WeakKeyIter<'a, K, V>
+where
+    K: WeakKey,
+{
+    type Item = (K::Strong, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let bucket = self.buckets.get(self.bucket)?;
+            match bucket.get(self.item) {
+                Some((weak, value, _)) => {
+                    self.item += 1;
+                    if let Some(strong) = weak.upgrade() {
+                        break Some((strong, value));
+                    }
+                }
+                None => {
+                    self.bucket += 1;
+                    self.item = 0;
+                }
+            }
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a WeakKeyHashMap<K, V>
+where
+    K: WeakKey,
+{
+    type Item = (K::Strong, &'a V);
+    type IntoIter = WeakKeyIter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        WeakKeyIter {
+            buckets: &self.buckets,
+            bucket: 0,
+            item: 0,
+        }
+    }
+}
+
+/// A view into a single slot of a [`WeakKeyHashMap`], as returned by
+/// [`WeakKeyHashMap::entry`].
+pub enum WeakKeyEntry<'a, K: WeakKey, V> {
+    Occupied(WeakKeyOccupiedEntry<'a, V>),
+    Vacant(WeakKeyVacantEntry<'a, K, V>),
+}
+
+impl<'a, K: WeakKey, V> WeakKeyEntry<'a, K, V> {
+    pub fn or_insert(self, value: V) -> &'a mut V {
+        match self {
+            WeakKeyEntry::Occupied(e) => e.into_mut(),
+            WeakKeyEntry::Vacant(e) => e.insert(value),
+        }
+    }
+
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            WeakKeyEntry::Occupied(e) => e.into_mut(),
+            WeakKeyEntry::Vacant(e) => e.insert(default()),
+        }
+    }
+}
+
+pub struct WeakKeyOccupiedEntry<'a, V> {
+    value: &'a mut V,
+}
+
+impl<'a, V> WeakKeyOccupiedEntry<'a, V> {
+    pub fn into_mut(self) -> &'a mut V {
+        self.value
+    }
+}
+
+pub struct WeakKeyVacantEntry<'a, K: WeakKey, V> {
+    map: &'a mut WeakKeyHashMap<K, V>,
+    weak: K,
+    hash: u64,
+    bucket: usize,
+}
+
+impl<'a, K: WeakKey, V> WeakKeyVacantEntry<'a, K, V> {
+    pub fn insert(self, value: V) -> &'a mut V {
+        let WeakKeyVacantEntry {
+            map,
+            weak,
+            hash,
+            bucket,
+        } = self;
+        map.buckets[bucket].push((weak, value, hash));
+        map.live += 1;
+        &mut map.buckets[bucket].last_mut().unwrap().1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weak_key_hashmap_insert_and_get() {
+        // Setup
+        let mut map: WeakKeyHashMap<RcWeak<str>, u32> = WeakKeyHashMap::new();
+        let key: Rc<str> = Rc::from("poneyland");
+
+        // Scenario: A key held alive elsewhere is found by value
+        map.insert(&key, 3);
+        assert_eq!(map.get("poneyland"), Some(&3));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn weak_key_hashmap_drops_entry_when_key_expires() {
+        // Setup
+        let mut map: WeakKeyHashMap<RcWeak<str>, u32> = WeakKeyHashMap::new();
+        let key: Rc<str> = Rc::from("poneyland");
+        map.insert(&key, 3);
+        drop(key);
+
+        // Scenario: Once the last strong owner drops, the entry can no longer be upgraded
+        assert_eq!(map.get("poneyland"), None);
+
+        // Scenario: Sweeping reclaims the now-dead slot
+        map.remove_expired();
+        assert_eq!(map.len(), 0);
+    }
+
+    #[test]
+    fn weak_key_hashmap_insert_reclaims_expired_slot() {
+        // Setup
+        let mut map: WeakKeyHashMap<RcWeak<str>, u32> = WeakKeyHashMap::new();
+        let first: Rc<str> = Rc::from("poneyland");
+        map.insert(&first, 3);
+        drop(first);
+
+        // Scenario: Inserting a different key that lands in the same bucket reclaims the
+        // expired slot (here through the resize this insert triggers, which sweeps expired
+        // entries) instead of growing forever
+        let second: Rc<str> = Rc::from("poneyland");
+        map.insert(&second, 6);
+        assert_eq!(map.get("poneyland"), Some(&6));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn weak_key_hashmap_insert_reclaim_does_not_inflate_len() {
+        // Setup: enough distinct keys that the table has grown past the point where every
+        // insert forces a `resize()` (which would otherwise sweep out the expired slot before
+        // the in-place reclaim path below ever runs)
+        let mut map: WeakKeyHashMap<RcWeak<str>, u32> = WeakKeyHashMap::new();
+        let mut keys = Vec::new();
+        for i in 0..20 {
+            let key: Rc<str> = Rc::from(i.to_string());
+            map.insert(&key, i);
+            keys.push(key);
+        }
+        let stale = keys.pop().unwrap();
+        let stale_text = stale.to_string();
+        drop(stale);
+
+        // Scenario: Reinserting a key with the same pointee value lands in the same slot, hits
+        // the expired entry during the scan, and reclaims it in place; `len` must reflect only
+        // entries that are actually still live, not double-count the reclaimed slot
+        let replacement: Rc<str> = Rc::from(stale_text.as_str());
+        map.insert(&replacement, 100);
+
+        let actual_live: usize = (&map).into_iter().count();
+        assert_eq!(map.len(), actual_live);
+    }
+
+    #[test]
+    fn weak_key_hashmap_entry_or_insert() {
+        // Setup
+        let mut map: WeakKeyHashMap<RcWeak<str>, u32> = WeakKeyHashMap::new();
+        let key: Rc<str> = Rc::from("poneyland");
+
+        // Scenario: A vacant entry inserts a new value
+        *map.entry(&key).or_insert(3) += 0;
+        assert_eq!(map.get("poneyland"), Some(&3));
+
+        // Scenario: An occupied entry is mutated in place instead of being overwritten
+        *map.entry(&key).or_insert(10) *= 2;
+        assert_eq!(map.get("poneyland"), Some(&6));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn weak_key_hashmap_entry_reclaims_expired_slot() {
+        // Setup
+        let mut map: WeakKeyHashMap<RcWeak<str>, u32> = WeakKeyHashMap::new();
+        let first: Rc<str> = Rc::from("poneyland");
+        map.insert(&first, 3);
+        drop(first);
+
+        // Scenario: `entry` on an expired key is vacant, not occupied, and reclaims the slot
+        let second: Rc<str> = Rc::from("poneyland");
+        map.entry(&second).or_insert(6);
+        assert_eq!(map.get("poneyland"), Some(&6));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn weak_key_hashmap_iterates_live_entries() {
+        // Setup
+        let mut map: WeakKeyHashMap<RcWeak<str>, u32> = WeakKeyHashMap::new();
+        let a: Rc<str> = Rc::from("a");
+        let b: Rc<str> = Rc::from("b");
+        map.insert(&a, 1);
+        map.insert(&b, 2);
+        drop(b);
+
+        // Scenario: Iterating only yields entries whose key is still alive
+        let collected: Vec<_> = (&map).into_iter().map(|(k, &v)| (k.to_string(), v)).collect();
+        assert_eq!(collected, vec![("a".to_string(), 1)]);
+    }
+}